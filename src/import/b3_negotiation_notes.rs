@@ -0,0 +1,40 @@
+use super::generic_csv::{ColumnMapping, GenericCsv};
+use super::{ImportError, TransactionSource};
+use crate::wallet::Event;
+use std::io::Read;
+
+pub struct B3NegotiationNotes {
+    inner: GenericCsv,
+}
+
+impl B3NegotiationNotes {
+    pub fn new() -> Self {
+        let inner = GenericCsv::new(
+            ColumnMapping {
+                date: 0,
+                code: 1,
+                action: 2,
+                amount: 3,
+                price: 4,
+            },
+            "%d/%m/%Y",
+            vec!["C".to_owned()],
+            vec!["V".to_owned()],
+        )
+        .with_delimiter(b';');
+
+        Self { inner }
+    }
+}
+
+impl Default for B3NegotiationNotes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TransactionSource for B3NegotiationNotes {
+    fn parse<R: Read>(&self, reader: R) -> Result<Vec<Event>, ImportError> {
+        self.inner.parse(reader)
+    }
+}
@@ -0,0 +1,189 @@
+use super::{ImportError, TransactionSource};
+use crate::money::Money;
+use crate::wallet::{Currency, Event, TransactionInfo};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use std::io::Read;
+
+fn parse_date(value: &str, format: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
+    match NaiveDateTime::parse_from_str(value, format) {
+        Ok(naive) => Ok(naive.and_utc()),
+        Err(_) => NaiveDate::parse_from_str(value, format)
+            .map(|date| date.and_hms_opt(0, 0, 0).expect("midnight is always valid").and_utc()),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnMapping {
+    pub date: usize,
+    pub code: usize,
+    pub action: usize,
+    pub amount: usize,
+    pub price: usize,
+}
+
+pub struct GenericCsv {
+    columns: ColumnMapping,
+    date_format: String,
+    delimiter: u8,
+    buy_tokens: Vec<String>,
+    sell_tokens: Vec<String>,
+}
+
+impl GenericCsv {
+    pub fn new(
+        columns: ColumnMapping,
+        date_format: impl Into<String>,
+        buy_tokens: Vec<String>,
+        sell_tokens: Vec<String>,
+    ) -> Self {
+        Self {
+            columns,
+            date_format: date_format.into(),
+            delimiter: b',',
+            buy_tokens,
+            sell_tokens,
+        }
+    }
+
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn investidor10() -> Self {
+        Self::new(
+            ColumnMapping {
+                date: 0,
+                code: 1,
+                action: 2,
+                amount: 3,
+                price: 4,
+            },
+            "%d/%m/%Y %H:%M:%S",
+            vec!["B".to_owned()],
+            vec!["S".to_owned()],
+        )
+    }
+
+    fn max_column(&self) -> usize {
+        [
+            self.columns.date,
+            self.columns.code,
+            self.columns.action,
+            self.columns.amount,
+            self.columns.price,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+    }
+}
+
+impl TransactionSource for GenericCsv {
+    fn parse<R: Read>(&self, reader: R) -> Result<Vec<Event>, ImportError> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(self.delimiter)
+            .double_quote(false)
+            .flexible(true)
+            .from_reader(reader);
+
+        let mut events = Vec::new();
+        for result in rdr.records() {
+            let record = result?;
+            if record.len() <= self.max_column() {
+                return Err(ImportError::MissingColumn(record.len(), self.max_column() + 1));
+            }
+
+            let date_str = &record[self.columns.date];
+            let date = parse_date(date_str, &self.date_format)
+                .map_err(|e| ImportError::InvalidDate(date_str.to_owned(), e))?;
+
+            let code = record[self.columns.code].to_owned();
+
+            let amount_str = &record[self.columns.amount];
+            let amount: i32 = amount_str
+                .parse()
+                .map_err(|_| ImportError::InvalidAmount(amount_str.to_owned()))?;
+
+            let price_str = &record[self.columns.price];
+            let price: Currency =
+                Money::parse(price_str).map_err(|_| ImportError::InvalidPrice(price_str.to_owned()))?;
+
+            let action = record[self.columns.action].trim();
+            let info = TransactionInfo::new(date, amount, price);
+            if self.buy_tokens.iter().any(|token| token == action) {
+                events.push(Event::Buy(code, info));
+            } else if self.sell_tokens.iter().any(|token| token == action) {
+                events.push(Event::Sell(code, info));
+            } else {
+                return Err(ImportError::UnknownAction(action.to_owned()));
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::B3NegotiationNotes;
+
+    #[test]
+    fn it_parses_investidor10_rows_with_a_full_timestamp() {
+        let csv = "15/01/2024 14:30:00,PETR4,B,100,14.50\n20/02/2024 09:00:00,PETR4,S,50,16.00\n";
+        let events = GenericCsv::investidor10().parse(csv.as_bytes()).unwrap();
+
+        assert!(matches!(&events[0], Event::Buy(code, info) if code == "PETR4" && info.amount() == 100));
+        assert!(matches!(&events[1], Event::Sell(code, info) if code == "PETR4" && info.amount() == 50));
+    }
+
+    #[test]
+    fn it_recognizes_multiple_buy_and_sell_tokens() {
+        let columns = ColumnMapping {
+            date: 0,
+            code: 1,
+            action: 2,
+            amount: 3,
+            price: 4,
+        };
+        let source = GenericCsv::new(
+            columns,
+            "%d/%m/%Y",
+            vec!["BUY".to_owned(), "B".to_owned()],
+            vec!["SELL".to_owned(), "S".to_owned()],
+        );
+
+        let csv = "15/01/2024,PETR4,BUY,100,14.50\n16/01/2024,PETR4,SELL,50,16.00\n";
+        let events = source.parse(csv.as_bytes()).unwrap();
+
+        assert!(matches!(events[0], Event::Buy(_, _)));
+        assert!(matches!(events[1], Event::Sell(_, _)));
+    }
+
+    #[test]
+    fn it_rejects_a_row_with_too_few_columns() {
+        let csv = "15/01/2024 14:30:00,PETR4,B,100\n";
+        let result = GenericCsv::investidor10().parse(csv.as_bytes());
+
+        assert!(matches!(result, Err(ImportError::MissingColumn(4, 5))));
+    }
+
+    #[test]
+    fn it_rejects_an_unrecognized_action_token() {
+        let csv = "15/01/2024 14:30:00,PETR4,X,100,14.50\n";
+        let result = GenericCsv::investidor10().parse(csv.as_bytes());
+
+        assert!(matches!(result, Err(ImportError::UnknownAction(token)) if token == "X"));
+    }
+
+    #[test]
+    fn it_parses_semicolon_delimited_b3_negotiation_notes_with_date_only_rows() {
+        let csv = "15/01/2024;PETR4;C;100;14,50\n16/01/2024;PETR4;V;50;16,00\n";
+        let events = B3NegotiationNotes::new().parse(csv.as_bytes()).unwrap();
+
+        assert!(matches!(&events[0], Event::Buy(code, _) if code == "PETR4"));
+        assert!(matches!(&events[1], Event::Sell(code, _) if code == "PETR4"));
+    }
+}
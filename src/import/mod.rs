@@ -0,0 +1,34 @@
+use crate::wallet::Event;
+use std::io::Read;
+use thiserror::Error;
+
+pub mod b3_negotiation_notes;
+pub mod generic_csv;
+
+pub use b3_negotiation_notes::B3NegotiationNotes;
+pub use generic_csv::{ColumnMapping, GenericCsv};
+
+pub trait TransactionSource {
+    fn parse<R: Read>(&self, reader: R) -> Result<Vec<Event>, ImportError>;
+}
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error("invalid date {0:?}: {1}")]
+    InvalidDate(String, chrono::ParseError),
+
+    #[error("invalid amount {0:?}")]
+    InvalidAmount(String),
+
+    #[error("invalid price {0:?}")]
+    InvalidPrice(String),
+
+    #[error("unrecognized buy/sell token {0:?}")]
+    UnknownAction(String),
+
+    #[error("row has {0} columns, but the column mapping expects at least {1}")]
+    MissingColumn(usize, usize),
+}
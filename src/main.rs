@@ -1,94 +1,77 @@
-use chrono::{DateTime, Utc};
-use online::{investidor10::{Investidor10Api}, OnlineWallet};
-use serde::Deserialize;
+use import::{B3NegotiationNotes, GenericCsv, TransactionSource};
+use online::investidor10::Investidor10Api;
+use std::collections::HashSet;
 use std::error::Error;
-use wallet::{Currency, Event, TransactionInfo, Wallet};
+use std::fs::File;
+use wallet::{Currency, Event, Wallet};
 
+pub mod import;
+pub mod money;
 pub mod online;
+pub mod snapshot;
 pub mod stock;
 pub mod wallet;
 
-#[derive(Debug, Deserialize)]
-enum ActionEntry {
-    #[serde(rename = "S")]
-    Sell,
-    #[serde(rename = "B")]
-    Buy,
-}
+const SNAPSHOT_PATH: &str = "wallet.snapshot";
 
-#[derive(Debug, Deserialize)]
-struct CsvEntry {
-    #[serde(with = "custom_date_time")]
-    date: DateTime<Utc>,
-    code: String,
-    action: ActionEntry,
-    amount: i32,
-    price: Currency,
+enum ImportFormat {
+    Investidor10,
+    B3NegotiationNotes,
 }
 
-mod custom_date_time {
-    use chrono::{DateTime, TimeZone, Utc};
-    use serde::{Deserialize, Deserializer};
-    const FORMAT: &str = "%d/%m/%Y %H:%M:%S";
-
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        Utc.datetime_from_str(&s, FORMAT)
-            .map_err(serde::de::Error::custom)
-    }
-}
-
-impl From<CsvEntry> for Event {
-    fn from(val: CsvEntry) -> Self {
-        match val.action {
-            ActionEntry::Sell => Event::Sell(
-                val.code,
-                TransactionInfo::new(val.date, val.amount, val.price),
-            ),
-            ActionEntry::Buy => Event::Buy(
-                val.code,
-                TransactionInfo::new(val.date, val.amount, val.price),
-            ),
+impl ImportFormat {
+    fn from_arg(arg: Option<&str>) -> Self {
+        match arg {
+            Some("b3") => ImportFormat::B3NegotiationNotes,
+            _ => ImportFormat::Investidor10,
         }
     }
 }
 
-fn import_csv_to_entries<R: std::io::Read>(reader: R) -> Result<Vec<Event>, Box<dyn Error>> {
-    let mut rdr = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .delimiter(b',')
-        .double_quote(false)
-        .flexible(true)
-        .from_reader(reader);
-
-    let mut csv_entries = vec![];
-    for result in rdr.deserialize() {
-        let record: CsvEntry = result?;
-        csv_entries.push(record.into());
+fn load_cached_events() -> Vec<Event> {
+    match File::open(SNAPSHOT_PATH).map(snapshot::load_wallet) {
+        Ok(Ok(wallet)) => wallet.transactions().values().flatten().cloned().collect(),
+        _ => Vec::new(),
     }
+}
 
-    Ok(csv_entries)
+fn dedup_key(event: &Event) -> (bool, String, chrono::NaiveDate, i32, Currency) {
+    (
+        matches!(event, Event::Buy(_, _)),
+        event.code().to_owned(),
+        event.date().date_naive(),
+        event.amount(),
+        event.price(),
+    )
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    //["]([\d]+),([\d]+)["]
-    let entries = import_csv_to_entries(std::io::stdin()).unwrap();
-    let position = Wallet::from_transactions(entries);
+    let format = ImportFormat::from_arg(std::env::args().nth(1).as_deref());
 
-    let investidor10 = Investidor10Api::new("eyJpdiI6Im54cDVZekJlYU1BdGppaXMvNjZmV0E9PSIsInZhbHVlIjoiMEFETmwxUFRhMnJyZ2RtK0Y2dU9tZ3hpYjNJekV2SlJJUFhjTHdpZm5wSzJzQW9qOHVsRWdGYnllRUNzM0tSbXEwUnk1V1FRcE4zL0RkNlV5QmFKb2FacVUrRk9EOFk4OUJTQm9hV2JnZUsrR3hLaVBnSHJWQTZSRGlhc2RmdEsiLCJtYWMiOiI0ZjcwMGQwMjgwYTVmOGRkNzQ2NzBkNzNhODE5YmE5Y2JkYzQxOWJmZTgzZTMzZDk2ZTUwZmI5N2RjYTI2OGNjIn0%3D", 194632);
-    for ticker in position.wealth() {
-        for event in ticker.events() {
-            if (investidor10.add_asset(event.clone()).await).is_err() {
-                println!("{:?} failed to be added", event);
-            } else {
-                println!("{:?} added with success", event);
-            }
+    let entries = match format {
+        ImportFormat::Investidor10 => GenericCsv::investidor10().parse(std::io::stdin())?,
+        ImportFormat::B3NegotiationNotes => B3NegotiationNotes::new().parse(std::io::stdin())?,
+    };
+
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    for event in load_cached_events().into_iter().chain(entries) {
+        if seen.insert(dedup_key(&event)) {
+            merged.push(event);
         }
     }
 
+    let position = Wallet::from_transactions(merged);
+
+    let investidor10 = Investidor10Api::new("eyJpdiI6Im54cDVZekJlYU1BdGppaXMvNjZmV0E9PSIsInZhbHVlIjoiMEFETmwxUFRhMnJyZ2RtK0Y2dU9tZ3hpYjNJekV2SlJJUFhjTHdpZm5wSzJzQW9qOHVsRWdGYnllRUNzM0tSbXEwUnk1V1FRcE4zL0RkNlV5QmFKb2FacVUrRk9EOFk4OUJTQm9hV2JnZUsrR3hLaVBnSHJWQTZSRGlhc2RmdEsiLCJtYWMiOiI0ZjcwMGQwMjgwYTVmOGRkNzQ2NzBkNzNhODE5YmE5Y2JkYzQxOWJmZTgzZTMzZDk2ZTUwZmI5N2RjYTI2OGNjIn0%3D", 194632);
+    let report = position.sync(&investidor10).await?;
+    println!("{} event(s) pushed", report.pushed);
+    for (event, error) in &report.failed {
+        eprintln!("failed to push {} ({}): {error}", event.code(), event.date());
+    }
+
+    snapshot::save_wallet(&position, File::create(SNAPSHOT_PATH)?)?;
+
     Ok(())
 }
@@ -0,0 +1,229 @@
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
+
+const DECIMALS: u32 = 8;
+const SCALE: i64 = 10i64.pow(DECIMALS);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub fn from_scaled(scaled: i64) -> Self {
+        Money(scaled)
+    }
+
+    pub fn scaled(self) -> i64 {
+        self.0
+    }
+
+    pub fn from_f64(value: f64) -> Self {
+        Money((value * SCALE as f64).round() as i64)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+
+    pub fn to_investidor10_string(self) -> String {
+        let negative = self.0 < 0;
+        let magnitude = self.0.unsigned_abs();
+        let units = magnitude / SCALE as u64;
+        let fraction = magnitude % SCALE as u64;
+
+        format!(
+            "{}{},{:0width$}",
+            if negative { "-" } else { "" },
+            units,
+            fraction,
+            width = DECIMALS as usize
+        )
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        Self::parse_decimal_str(s)
+    }
+
+    fn parse_decimal_str(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let normalized = s.replace(',', ".");
+        let (sign, rest) = match normalized.strip_prefix('-') {
+            Some(rest) => (-1i64, rest),
+            None => (1i64, normalized.as_str()),
+        };
+
+        let mut parts = rest.splitn(2, '.');
+        let integer_part = parts.next().unwrap_or("0");
+        let fraction_part = parts.next().unwrap_or("");
+
+        if fraction_part.len() > DECIMALS as usize {
+            return Err(format!("too many decimal places in money value {s:?}"));
+        }
+
+        let integer: i64 = integer_part
+            .parse()
+            .map_err(|_| format!("invalid money value {s:?}"))?;
+        let mut fraction: i64 = if fraction_part.is_empty() {
+            0
+        } else {
+            fraction_part
+                .parse()
+                .map_err(|_| format!("invalid money value {s:?}"))?
+        };
+        fraction *= 10i64.pow(DECIMALS - fraction_part.len() as u32);
+
+        Ok(Money(sign * (integer * SCALE + fraction)))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.*}", DECIMALS as usize, self.to_f64())
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, rhs: Self) -> Self::Output {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl Mul<i32> for Money {
+    type Output = Money;
+    fn mul(self, rhs: i32) -> Self::Output {
+        Money(self.0 * rhs as i64)
+    }
+}
+
+impl Div<i32> for Money {
+    type Output = Money;
+    fn div(self, rhs: i32) -> Self::Output {
+        Money(self.0 / rhs as i64)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Self {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl From<f64> for Money {
+    fn from(value: f64) -> Self {
+        Money::from_f64(value)
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_investidor10_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(MoneyVisitor)
+    }
+}
+
+struct MoneyVisitor;
+
+impl<'de> Visitor<'de> for MoneyVisitor {
+    type Value = Money;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a JSON number or a decimal string, e.g. \"15,00\" or \"15.00\"")
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Money::from_f64(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Money(value * SCALE))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Money(value as i64 * SCALE))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Money::parse_decimal_str(value).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_json_numbers() {
+        assert_eq!(serde_json::from_str::<Money>("15.5").unwrap(), Money(1_550_000_000));
+    }
+
+    #[test]
+    fn it_parses_dot_decimal_strings() {
+        assert_eq!(Money::parse_decimal_str("15.50").unwrap(), Money::from_f64(15.5));
+    }
+
+    #[test]
+    fn it_parses_comma_decimal_strings() {
+        assert_eq!(Money::parse_decimal_str("15,50").unwrap(), Money::from_f64(15.5));
+    }
+
+    #[test]
+    fn it_formats_in_the_investidor10_wire_format() {
+        assert_eq!(Money::from_f64(15.0).to_investidor10_string(), "15,00000000");
+    }
+
+    #[test]
+    fn it_accumulates_without_floating_point_drift() {
+        let total: Money = std::iter::repeat_n(Money::from_f64(0.1), 10).sum();
+        assert_eq!(total, Money::from_f64(1.0));
+    }
+
+    #[test]
+    fn it_serializes_as_a_decimal_string_not_a_lossy_f64() {
+        let value = Money::from_f64(15.5);
+        let json = serde_json::to_string(&value).unwrap();
+
+        assert_eq!(json, "\"15,50000000\"");
+        assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), value);
+    }
+}
@@ -1,8 +1,17 @@
+use super::rate_limit::{RateLimitConfig, RateLimiter};
 use super::{AssetType, OnlineWallet, Ticker};
-use crate::wallet::{Code, Event};
+use crate::wallet::{Code, Currency, Event, TransactionInfo};
+use chrono::{DateTime, Utc};
 use futures::future::BoxFuture;
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+use reqwest::{RequestBuilder, Response, StatusCode};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const MAX_RETRIES: u32 = 3;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Serialize)]
 pub struct Trade {
@@ -16,36 +25,109 @@ pub struct Trade {
     date: String,
     qty: i32,
     ticker: i32,
-    #[serde(with = "custom_f64")]
-    price: f64,
+    #[serde(with = "custom_money")]
+    price: Currency,
     cost: f32,
 }
 
-mod custom_f64 {
+mod custom_money {
+    use crate::wallet::Currency;
     use serde::Serializer;
-    pub fn serialize<S>(f: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S>(value: &Currency, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let s = format!("{:.2}", f);
-        let s = format!("{}000000", s.replace('.', ","));
-        serializer.serialize_str(&s)
+        serializer.serialize_str(&value.to_investidor10_string())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RemoteTrade {
+    ticker: Code,
+    #[serde(rename = "type")]
+    trade_type: String,
+    #[serde(with = "remote_date")]
+    date: DateTime<Utc>,
+    qty: i32,
+    price: Currency,
+}
+
+mod remote_date {
+    use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+    use serde::{Deserialize, Deserializer};
+    const FORMAT: &str = "%d/%m/%Y";
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        let date = NaiveDate::parse_from_str(&s, FORMAT).map_err(serde::de::Error::custom)?;
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is always valid");
+        Ok(Utc.from_utc_datetime(&midnight))
+    }
+}
+
+impl TryFrom<RemoteTrade> for Event {
+    type Error = Investidor10Error;
+
+    fn try_from(value: RemoteTrade) -> Result<Self, Self::Error> {
+        let info = TransactionInfo::new(value.date, value.qty, value.price);
+        match value.trade_type.as_str() {
+            "BUY" => Ok(Event::Buy(value.ticker, info)),
+            "SELL" => Ok(Event::Sell(value.ticker, info)),
+            other => Err(Investidor10Error::UnknownTradeType(other.to_string())),
+        }
     }
 }
 
 pub struct Investidor10Api {
+    client: reqwest::Client,
     headers: HeaderMap,
     wallet_id: i32,
+    ticker_cache: Mutex<HashMap<Code, Ticker>>,
+    rate_limiter: RateLimiter,
 }
 impl Investidor10Api {
     pub fn new(session: &str, wallet_id: i32) -> Self {
+        Self::with_rate_limit(session, wallet_id, RateLimitConfig::default())
+    }
+
+    pub fn with_rate_limit(session: &str, wallet_id: i32, rate_limit: RateLimitConfig) -> Self {
         let laravel_session = format!("laravel_session={}", session);
         let mut headers = HeaderMap::new();
         headers.insert(USER_AGENT, "reqwest".parse().unwrap());
         headers.insert("Content-Type", HeaderValue::from_static("application/json"));
         headers.insert("Cookie", HeaderValue::from_str(&laravel_session).unwrap());
 
-        Investidor10Api { headers, wallet_id }
+        Investidor10Api {
+            client: reqwest::Client::new(),
+            headers,
+            wallet_id,
+            ticker_cache: Mutex::new(HashMap::new()),
+            rate_limiter: RateLimiter::new(rate_limit),
+        }
+    }
+
+    async fn send_with_retry(
+        &self,
+        build_request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, Investidor10Error> {
+        let mut delay = INITIAL_RETRY_DELAY;
+
+        for attempt in 0..=MAX_RETRIES {
+            self.rate_limiter.acquire().await;
+            let response = build_request().send().await?;
+
+            if !is_retryable_status(response.status()) || attempt == MAX_RETRIES {
+                return Ok(response);
+            }
+
+            tokio::time::sleep(delay).await;
+            delay = delay.mul_f64(self.rate_limiter.backoff_multiplier());
+        }
+
+        unreachable!("loop always returns on its last iteration")
     }
 
     async fn create_trade_request(&self, event: &Event) -> Result<Trade, Investidor10Error> {
@@ -82,11 +164,8 @@ impl Investidor10Api {
             ticker
         );
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(url_ticker)
-            .headers(self.headers.clone())
-            .send()
+        let response = self
+            .send_with_retry(|| self.client.get(&url_ticker).headers(self.headers.clone()))
             .await?;
 
         let trade: Vec<TickerInfo> = serde_json::from_str(&response.text().await?)?;
@@ -107,11 +186,8 @@ impl Investidor10Api {
             ticker
         );
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(url_ticker)
-            .headers(self.headers.clone())
-            .send()
+        let response = self
+            .send_with_retry(|| self.client.get(&url_ticker).headers(self.headers.clone()))
             .await?;
 
         serde_json::from_str::<Vec<TickerInfo>>(&response.text().await?)?
@@ -135,13 +211,14 @@ impl OnlineWallet for Investidor10Api {
                 self.wallet_id
             );
 
-            let client = reqwest::Client::new();
             let json = serde_json::to_string(&trade)?;
-            let response = client
-                .post(url)
-                .headers(self.headers.clone())
-                .body(json)
-                .send()
+            let response = self
+                .send_with_retry(|| {
+                    self.client
+                        .post(&url)
+                        .headers(self.headers.clone())
+                        .body(json.clone())
+                })
                 .await?;
 
             response.error_for_status()?;
@@ -149,12 +226,39 @@ impl OnlineWallet for Investidor10Api {
         })
     }
 
-    fn get_ticker_id(&self, ticker: &Code) -> BoxFuture<'_, Result<Ticker, Self::Error>> {
-        let ticker = ticker.clone();
+    fn get_ticker_id(&self, ticker: &str) -> BoxFuture<'_, Result<Ticker, Self::Error>> {
+        let ticker: Code = ticker.to_owned();
+        Box::pin(async move {
+            if let Some(cached) = self.ticker_cache.lock().unwrap().get(&ticker) {
+                return Ok(cached.clone());
+            }
+
+            let resolved = match self.get_as_ticker(&ticker).await {
+                Ok(resolved) => resolved,
+                Err(_) => self.get_as_fii(&ticker).await?,
+            };
+
+            self.ticker_cache
+                .lock()
+                .unwrap()
+                .insert(ticker, resolved.clone());
+            Ok(resolved)
+        })
+    }
+
+    fn fetch_trades(&self) -> BoxFuture<'_, Result<Vec<Event>, Self::Error>> {
         Box::pin(async move {
-            self.get_as_ticker(&ticker)
-                .await
-                .or(self.get_as_fii(&ticker).await)
+            let url = format!(
+                "https://investidor10.com.br/api/minhas-carteiras/lancamentos/{}/",
+                self.wallet_id
+            );
+
+            let response = self
+                .send_with_retry(|| self.client.get(&url).headers(self.headers.clone()))
+                .await?;
+
+            let remote_trades: Vec<RemoteTrade> = serde_json::from_str(&response.text().await?)?;
+            remote_trades.into_iter().map(Event::try_from).collect()
         })
     }
 }
@@ -169,10 +273,73 @@ pub enum Investidor10Error {
 
     #[error("Ticker {0} not found")]
     TickerNotFound(String),
+
+    #[error("unknown trade type {0}")]
+    UnknownTradeType(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TickerInfo {
     id: i32,
     name: String,
-}
\ No newline at end of file
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_only_retries_on_429_and_5xx() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_retryable_status(StatusCode::OK));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn it_returns_a_cached_ticker_without_issuing_a_network_request() {
+        let api = Investidor10Api::new("session", 1);
+        api.ticker_cache.lock().unwrap().insert(
+            "PETR4".to_owned(),
+            Ticker {
+                id: 42,
+                _name: "Petrobras".to_owned(),
+                r#type: AssetType::Ticker,
+            },
+        );
+
+        let ticker = api.get_ticker_id("PETR4").await.unwrap();
+        assert_eq!(ticker.id, 42);
+    }
+
+    #[test]
+    fn it_converts_a_remote_trade_into_an_event() {
+        let json = r#"[
+            {"ticker": "PETR4", "type": "BUY", "date": "15/01/2024", "qty": 100, "price": 14.5},
+            {"ticker": "PETR4", "type": "SELL", "date": "20/02/2024", "qty": 50, "price": 16.0}
+        ]"#;
+
+        let remote_trades: Vec<RemoteTrade> = serde_json::from_str(json).unwrap();
+        let events: Vec<Event> = remote_trades
+            .into_iter()
+            .map(Event::try_from)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert!(matches!(&events[0], Event::Buy(code, _) if code == "PETR4"));
+        assert!(matches!(&events[1], Event::Sell(code, _) if code == "PETR4"));
+    }
+
+    #[test]
+    fn it_rejects_an_unknown_remote_trade_type() {
+        let json = r#"[{"ticker": "PETR4", "type": "SPLIT", "date": "15/01/2024", "qty": 100, "price": 14.5}]"#;
+        let remote_trades: Vec<RemoteTrade> = serde_json::from_str(json).unwrap();
+
+        let result: Result<Vec<Event>, _> = remote_trades.into_iter().map(Event::try_from).collect();
+        assert!(matches!(result, Err(Investidor10Error::UnknownTradeType(t)) if t == "SPLIT"));
+    }
+}
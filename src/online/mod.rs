@@ -1,9 +1,10 @@
-use crate::wallet::{Code, Event};
+use crate::wallet::Event;
 use futures::future::BoxFuture;
 
 pub mod investidor10;
+pub mod rate_limit;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AssetType {
     Fii,
     Ticker,
@@ -13,12 +14,14 @@ pub enum AssetType {
 pub struct Ticker {
     id: i32,
     _name: String,
-    r#type: AssetType
+    r#type: AssetType,
 }
 
 pub trait OnlineWallet {
     type Error: std::error::Error;
 
     fn add_asset(&self, event: Event) -> BoxFuture<'_, Result<(), Self::Error>>;
-    fn get_ticker_id(&self, ticker: &Code) -> BoxFuture<'_, Result<Ticker, Self::Error>>;
+    fn get_ticker_id(&self, ticker: &str) -> BoxFuture<'_, Result<Ticker, Self::Error>>;
+
+    fn fetch_trades(&self) -> BoxFuture<'_, Result<Vec<Event>, Self::Error>>;
 }
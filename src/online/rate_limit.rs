@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+    pub max_requests: usize,
+    pub interval: Duration,
+    pub backoff_multiplier: f64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_requests: 60,
+            interval: Duration::from_secs(60),
+            backoff_multiplier: 2.0,
+        }
+    }
+}
+
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    sent_at: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            sent_at: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn backoff_multiplier(&self) -> f64 {
+        self.config.backoff_multiplier
+    }
+
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut sent_at = self.sent_at.lock().unwrap();
+                let now = Instant::now();
+                while matches!(sent_at.front(), Some(t) if now.duration_since(*t) >= self.config.interval)
+                {
+                    sent_at.pop_front();
+                }
+
+                if sent_at.len() < self.config.max_requests {
+                    sent_at.push_back(now);
+                    None
+                } else {
+                    let oldest = *sent_at.front().expect("len checked above");
+                    Some(self.config.interval - now.duration_since(oldest))
+                }
+            };
+
+            match wait {
+                None => return,
+                Some(delay) => tokio::time::sleep(delay).await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_allows_a_burst_up_to_max_requests_without_waiting() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_requests: 3,
+            interval: Duration::from_secs(60),
+            backoff_multiplier: 2.0,
+        });
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn it_waits_for_the_window_to_free_up_before_allowing_another_request() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_requests: 1,
+            interval: Duration::from_millis(50),
+            backoff_multiplier: 2.0,
+        });
+
+        limiter.acquire().await;
+        let start = Instant::now();
+        limiter.acquire().await;
+
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::money::Money;
+use crate::wallet::{Code, Event, TransactionInfo, Transactions, Wallet};
+use chrono::{TimeZone, Utc};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+const FORMAT_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EventKind {
+    Buy,
+    Sell,
+}
+
+impl From<EventKind> for u8 {
+    fn from(kind: EventKind) -> Self {
+        match kind {
+            EventKind::Buy => 0,
+            EventKind::Sell => 1,
+        }
+    }
+}
+
+impl TryFrom<u8> for EventKind {
+    type Error = SnapshotError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(EventKind::Buy),
+            1 => Ok(EventKind::Sell),
+            other => Err(SnapshotError::UnknownEventKind(other)),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("unsupported snapshot format version {0}, this build understands version {FORMAT_VERSION}")]
+    UnsupportedVersion(u8),
+
+    #[error("unknown event discriminant {0}")]
+    UnknownEventKind(u8),
+
+    #[error("timestamp {0} is out of range")]
+    InvalidTimestamp(i64),
+}
+
+pub fn save_wallet<W: Write>(wallet: &Wallet, mut writer: W) -> Result<(), SnapshotError> {
+    writer.write_all(&[FORMAT_VERSION])?;
+
+    let transactions = wallet.transactions();
+    writer.write_all(&(transactions.len() as u32).to_le_bytes())?;
+    for (code, events) in transactions {
+        write_string(&mut writer, code)?;
+        writer.write_all(&(events.len() as u32).to_le_bytes())?;
+        for event in events {
+            write_event(&mut writer, event)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn load_wallet<R: Read>(mut reader: R) -> Result<Wallet, SnapshotError> {
+    let mut version = [0u8; 1];
+    reader.read_exact(&mut version)?;
+    if version[0] != FORMAT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version[0]));
+    }
+
+    let code_count = read_u32(&mut reader)?;
+    let mut transactions: Transactions = HashMap::with_capacity(code_count as usize);
+    for _ in 0..code_count {
+        let code = read_string(&mut reader)?;
+        let event_count = read_u32(&mut reader)?;
+        let mut events = Vec::with_capacity(event_count as usize);
+        for _ in 0..event_count {
+            events.push(read_event(&mut reader, &code)?);
+        }
+        transactions.insert(code, events);
+    }
+
+    Ok(Wallet::from_raw_transactions(transactions))
+}
+
+fn write_event<W: Write>(writer: &mut W, event: &Event) -> Result<(), SnapshotError> {
+    let (kind, info): (EventKind, &TransactionInfo) = match event {
+        Event::Buy(_, info) => (EventKind::Buy, info),
+        Event::Sell(_, info) => (EventKind::Sell, info),
+    };
+
+    writer.write_all(&[u8::from(kind)])?;
+    writer.write_all(&info.date().timestamp().to_le_bytes())?;
+    writer.write_all(&info.amount().to_le_bytes())?;
+    writer.write_all(&info.price().scaled().to_le_bytes())?;
+    Ok(())
+}
+
+fn read_event<R: Read>(reader: &mut R, code: &Code) -> Result<Event, SnapshotError> {
+    let mut kind_byte = [0u8; 1];
+    reader.read_exact(&mut kind_byte)?;
+    let kind = EventKind::try_from(kind_byte[0])?;
+
+    let mut timestamp_bytes = [0u8; 8];
+    reader.read_exact(&mut timestamp_bytes)?;
+    let timestamp = i64::from_le_bytes(timestamp_bytes);
+    let date = Utc
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .ok_or(SnapshotError::InvalidTimestamp(timestamp))?;
+
+    let mut amount_bytes = [0u8; 4];
+    reader.read_exact(&mut amount_bytes)?;
+    let amount = i32::from_le_bytes(amount_bytes);
+
+    let mut price_bytes = [0u8; 8];
+    reader.read_exact(&mut price_bytes)?;
+    let price = Money::from_scaled(i64::from_le_bytes(price_bytes));
+
+    let info = TransactionInfo::new(date, amount, price);
+    Ok(match kind {
+        EventKind::Buy => Event::Buy(code.clone(), info),
+        EventKind::Sell => Event::Sell(code.clone(), info),
+    })
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, SnapshotError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn write_string<W: Write>(writer: &mut W, value: &str) -> Result<(), SnapshotError> {
+    writer.write_all(&(value.len() as u16).to_le_bytes())?;
+    writer.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, SnapshotError> {
+    let mut len_bytes = [0u8; 2];
+    reader.read_exact(&mut len_bytes)?;
+    let len = u16::from_le_bytes(len_bytes) as usize;
+
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(|e| SnapshotError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::TransactionInfo;
+
+    #[test]
+    fn it_round_trips_a_wallet_through_save_and_load() {
+        let events = vec![
+            Event::Buy(
+                "PETR4".to_owned(),
+                TransactionInfo::new(Utc.timestamp_opt(1_700_000_000, 0).unwrap(), 200, Money::from_f64(14.5)),
+            ),
+            Event::Sell(
+                "PETR4".to_owned(),
+                TransactionInfo::new(Utc.timestamp_opt(1_700_100_000, 0).unwrap(), 50, Money::from_f64(16.0)),
+            ),
+        ];
+        let wallet = Wallet::from_transactions(events);
+
+        let mut buffer = Vec::new();
+        save_wallet(&wallet, &mut buffer).unwrap();
+
+        let reloaded = load_wallet(buffer.as_slice()).unwrap();
+        let original_ticker = wallet.ticker("PETR4").unwrap();
+        let reloaded_ticker = reloaded.ticker("PETR4").unwrap();
+
+        assert_eq!(original_ticker.events().len(), reloaded_ticker.events().len());
+        assert_eq!(original_ticker.average_price(), reloaded_ticker.average_price());
+    }
+
+    #[test]
+    fn it_rejects_an_unsupported_format_version() {
+        let buffer = vec![FORMAT_VERSION + 1];
+        assert!(matches!(
+            load_wallet(buffer.as_slice()),
+            Err(SnapshotError::UnsupportedVersion(v)) if v == FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn it_rejects_an_unknown_event_discriminant() {
+        assert!(matches!(
+            EventKind::try_from(2u8),
+            Err(SnapshotError::UnknownEventKind(2))
+        ));
+    }
+}
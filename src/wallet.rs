@@ -1,11 +1,13 @@
-use chrono::{DateTime, Utc};
+use crate::money::Money;
+use crate::online::OnlineWallet;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::Deserialize;
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
 
 pub type Code = String;
-pub type Currency = f64;
+pub type Currency = Money;
 
-#[derive(Default, Debug, Deserialize)]
+#[derive(Default, Debug, Clone, Deserialize)]
 pub struct TransactionInfo {
     date: DateTime<Utc>,
     amount: i32,
@@ -30,7 +32,7 @@ impl TransactionInfo {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum Event {
     Sell(Code, TransactionInfo),
     Buy(Code, TransactionInfo),
@@ -44,13 +46,20 @@ impl Event {
         }
     }
 
+    pub fn date(&self) -> DateTime<Utc> {
+        match self {
+            Event::Sell(_, transaction) => transaction.date,
+            Event::Buy(_, transaction) => transaction.date,
+        }
+    }
+
     pub fn amount(&self) -> i32 {
         match self {
             Event::Sell(_, transaction) => transaction.amount,
             Event::Buy(_, transaction) => transaction.amount,
         }
     }
-    pub fn price(&self) -> f64 {
+    pub fn price(&self) -> Currency {
         match self {
             Event::Sell(_, transaction) => transaction.price,
             Event::Buy(_, transaction) => transaction.price,
@@ -86,12 +95,86 @@ impl Wallet {
             .map(|value| WalletTicker::new(value.0, value.1))
     }
 
+    pub(crate) fn from_raw_transactions(transactions: Transactions) -> Wallet {
+        Self { transactions }
+    }
+
+    pub(crate) fn transactions(&self) -> &Transactions {
+        &self.transactions
+    }
+
     pub fn wealth(&self) -> impl Iterator<Item = WalletTicker<'_>> {
         self.transactions
             .iter()
             .map(|x| WalletTicker::new(x.0, x.1))
             .filter(|x| x.position().is_some())
     }
+
+    pub fn realized_gains(
+        &self,
+        strategy: LotMatching,
+    ) -> Result<HashMap<Code, RealizedGains>, RealizedGainsError> {
+        self.transactions
+            .iter()
+            .map(|(code, events)| {
+                WalletTicker::new(code, events)
+                    .realized_gains(strategy)
+                    .map(|gains| (code.clone(), gains))
+            })
+            .collect()
+    }
+
+    pub async fn sync<W: OnlineWallet>(&self, online: &W) -> Result<SyncReport<W::Error>, W::Error> {
+        let remote = online.fetch_trades().await?;
+        let remote_keys: HashSet<_> = remote.iter().map(event_key).collect();
+
+        let mut report = SyncReport::default();
+        for events in self.transactions.values() {
+            for event in events {
+                if !remote_keys.contains(&event_key(event)) {
+                    match online.add_asset(event.clone()).await {
+                        Ok(()) => report.pushed += 1,
+                        Err(error) => report.failed.push((event.clone(), error)),
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+// One failed push shouldn't abort the rest of the batch: a single unrecognized
+// ticker or an exhausted retry budget would otherwise silently drop every
+// other not-yet-synced event for every other ticker.
+#[derive(Debug)]
+pub struct SyncReport<E> {
+    pub pushed: usize,
+    pub failed: Vec<(Event, E)>,
+}
+
+impl<E> Default for SyncReport<E> {
+    fn default() -> Self {
+        Self {
+            pushed: 0,
+            failed: Vec::new(),
+        }
+    }
+}
+
+// Keyed by calendar date rather than the full timestamp: the remote "lançamentos"
+// endpoint only reports a date, so comparing full `DateTime<Utc>`s would treat every
+// already-synced trade as missing and re-post it.
+type EventKey<'a> = (bool, &'a str, NaiveDate, i32, Currency);
+
+pub(crate) fn event_key(event: &Event) -> EventKey<'_> {
+    (
+        matches!(event, Event::Buy(_, _)),
+        event.code(),
+        event.date().date_naive(),
+        event.amount(),
+        event.price(),
+    )
 }
 
 #[derive(Debug)]
@@ -109,22 +192,27 @@ impl<'a> WalletTicker<'a> {
         self.name
     }
 
-    pub fn average_price(&self) -> f64 {
-        let calculated = self
+    pub fn events(&self) -> &'a [Event] {
+        self.events
+    }
+
+    pub fn average_price(&self) -> Currency {
+        let (total_amount, total_cost) = self
             .events
             .iter()
             .filter(|x| !matches!(x, Event::Sell(_, _)))
-            .fold((0.0, 0.0), |accumulated, item| {
-                (
-                    accumulated.0 + item.amount() as f64,
-                    accumulated.1 + item.amount() as f64 * item.price(),
-                )
+            .fold((0, Currency::ZERO), |(amount, cost), item| {
+                (amount + item.amount(), cost + item.price() * item.amount())
             });
 
-        calculated.1 / calculated.0
+        if total_amount == 0 {
+            return Currency::ZERO;
+        }
+
+        total_cost / total_amount
     }
 
-    pub fn position(&self) -> Option<Position> {
+    pub fn position(&self) -> Option<Position<'_>> {
         let amount = self.events.iter().fold(0, |accumulated, item| match item {
             Event::Sell(_, transaction_info) => accumulated - transaction_info.amount,
             Event::Buy(_, transaction_info) => accumulated + transaction_info.amount,
@@ -139,26 +227,163 @@ impl<'a> WalletTicker<'a> {
             _ => None,
         }
     }
+
+    pub fn realized_gains(&self, strategy: LotMatching) -> Result<RealizedGains, RealizedGainsError> {
+        let mut sorted: Vec<&Event> = self.events.iter().collect();
+        sorted.sort_by_key(|event| event.date());
+
+        match strategy {
+            LotMatching::AverageCost => self.realized_gains_average_cost(&sorted),
+            LotMatching::Fifo => self.realized_gains_fifo(&sorted),
+        }
+    }
+
+    fn realized_gains_average_cost(
+        &self,
+        events: &[&Event],
+    ) -> Result<RealizedGains, RealizedGainsError> {
+        let mut held = 0i32;
+        let mut average = Currency::ZERO;
+        let mut gains = RealizedGains::default();
+
+        for event in events {
+            match event {
+                Event::Buy(_, info) => {
+                    let cost = average * held + info.price() * info.amount();
+                    held += info.amount();
+                    average = if held == 0 { Currency::ZERO } else { cost / held };
+                }
+                Event::Sell(_, info) => {
+                    if info.amount() > held {
+                        return Err(RealizedGainsError::Oversold {
+                            code: self.name.to_owned(),
+                            sold: info.amount(),
+                            held,
+                        });
+                    }
+
+                    let cost_basis = average * info.amount();
+                    let realized = info.price() * info.amount() - cost_basis;
+                    held -= info.amount();
+
+                    gains.realized += realized;
+                    gains.cost_basis += cost_basis;
+                    gains.sales.push(RealizedSale {
+                        date: info.date(),
+                        amount: info.amount(),
+                        sell_price: info.price(),
+                        cost_basis,
+                        realized,
+                    });
+                }
+            }
+        }
+
+        Ok(gains)
+    }
+
+    fn realized_gains_fifo(&self, events: &[&Event]) -> Result<RealizedGains, RealizedGainsError> {
+        let mut lots: VecDeque<(i32, Currency)> = VecDeque::new();
+        let mut gains = RealizedGains::default();
+
+        for event in events {
+            match event {
+                Event::Buy(_, info) => lots.push_back((info.amount(), info.price())),
+                Event::Sell(_, info) => {
+                    let mut remaining = info.amount();
+                    let mut cost_basis = Currency::ZERO;
+
+                    while remaining > 0 {
+                        let Some((lot_amount, lot_price)) = lots.front_mut() else {
+                            let held: i32 = lots.iter().map(|(amount, _)| amount).sum::<i32>();
+                            return Err(RealizedGainsError::Oversold {
+                                code: self.name.to_owned(),
+                                sold: info.amount(),
+                                held: info.amount() - remaining + held,
+                            });
+                        };
+
+                        let consumed = remaining.min(*lot_amount);
+                        cost_basis += *lot_price * consumed;
+                        *lot_amount -= consumed;
+                        remaining -= consumed;
+
+                        if *lot_amount == 0 {
+                            lots.pop_front();
+                        }
+                    }
+
+                    let realized = info.price() * info.amount() - cost_basis;
+
+                    gains.realized += realized;
+                    gains.cost_basis += cost_basis;
+                    gains.sales.push(RealizedSale {
+                        date: info.date(),
+                        amount: info.amount(),
+                        sell_price: info.price(),
+                        cost_basis,
+                        realized,
+                    });
+                }
+            }
+        }
+
+        Ok(gains)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LotMatching {
+    AverageCost,
+    Fifo,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RealizedSale {
+    pub date: DateTime<Utc>,
+    pub amount: i32,
+    pub sell_price: Currency,
+    pub cost_basis: Currency,
+    pub realized: Currency,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RealizedGains {
+    pub realized: Currency,
+    pub cost_basis: Currency,
+    pub sales: Vec<RealizedSale>,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum RealizedGainsError {
+    #[error("tried to sell {sold} shares of {code} but only {held} were held")]
+    Oversold { code: Code, sold: i32, held: i32 },
 }
 
 #[derive(Debug)]
 pub struct Position<'a> {
     _code: &'a str,
     _current_amount: i32,
-    _average_price: f64,
+    _average_price: Currency,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::future::BoxFuture;
+    use std::sync::Mutex;
 
     const DATE: DateTime<Utc> = DateTime::<Utc>::MIN_UTC;
 
+    fn money(value: f64) -> Currency {
+        Currency::from_f64(value)
+    }
+
     pub fn create_actions() -> Vec<Event> {
         vec![
-            Event::Buy("PETR4".to_owned(), TransactionInfo::new(DATE, 200, 14.0)),
-            Event::Buy("PETR4".to_owned(), TransactionInfo::new(DATE, 300, 15.0)),
-            Event::Buy("PETR4".to_owned(), TransactionInfo::new(DATE, 400, 16.0)),
+            Event::Buy("PETR4".to_owned(), TransactionInfo::new(DATE, 200, money(14.0))),
+            Event::Buy("PETR4".to_owned(), TransactionInfo::new(DATE, 300, money(15.0))),
+            Event::Buy("PETR4".to_owned(), TransactionInfo::new(DATE, 400, money(16.0))),
         ]
     }
 
@@ -173,33 +398,43 @@ mod tests {
     #[test]
     fn it_can_calculate_the_average_price_for_a_ticker() {
         let position = Wallet::from_transactions(create_actions());
-        assert!((position.ticker("PETR4").unwrap().average_price().abs() - 15.22).abs() < 0.1);
+        assert!(
+            (position.ticker("PETR4").unwrap().average_price().to_f64() - 15.22).abs() < 0.1
+        );
     }
 
     #[test]
     fn it_will_return_none_if_there_is_no_transaction_regarding_a_key() {
         let position = Wallet::from_transactions(create_actions());
-        matches!(position.ticker("nonexistent"), None);
+        assert!(position.ticker("nonexistent").is_none());
     }
 
     #[test]
     fn it_should_ignore_sells_when_calculating_the_avg_price() {
         let actions = vec![
-            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, 20.0)),
-            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, 25.0)),
-            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(DATE, 50, 20.0)),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, money(20.0))),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, money(25.0))),
+            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(DATE, 50, money(20.0))),
         ];
 
         let ticker_position = WalletTicker::new("BBAS3", &actions);
-        assert!((ticker_position.average_price().abs() - 22.5).abs() < 0.1);
+        assert!((ticker_position.average_price().to_f64() - 22.5).abs() < 0.1);
+    }
+
+    #[test]
+    fn it_returns_zero_average_price_when_there_are_no_buys() {
+        let actions = vec![Event::Sell("BBAS3".to_owned(), TransactionInfo::new(DATE, 50, money(20.0)))];
+
+        let ticker_position = WalletTicker::new("BBAS3", &actions);
+        assert_eq!(ticker_position.average_price(), Currency::ZERO);
     }
 
     #[test]
     fn it_will_return_the_current_position_according_to_the_buys_and_sells() {
         let actions = vec![
-            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, 20.0)),
-            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, 25.0)),
-            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, 20.0)),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, money(20.0))),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, money(25.0))),
+            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, money(20.0))),
         ];
 
         let ticker_position = WalletTicker::new("BBAS3", &actions);
@@ -212,9 +447,9 @@ mod tests {
     #[test]
     fn it_will_return_none_if_all_the_stocks_were_sold() {
         let actions = vec![
-            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, 20.0)),
-            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, 25.0)),
-            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(DATE, 200, 20.0)),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, money(20.0))),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(DATE, 100, money(25.0))),
+            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(DATE, 200, money(20.0))),
         ];
 
         let ticker_position = WalletTicker::new("BBAS3", &actions);
@@ -222,4 +457,180 @@ mod tests {
 
         assert!(position.is_none());
     }
+
+    fn at(day: u32) -> DateTime<Utc> {
+        DATE + chrono::Duration::days(day as i64)
+    }
+
+    #[test]
+    fn it_calculates_realized_gains_with_average_cost() {
+        let actions = vec![
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(at(1), 100, money(20.0))),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(at(2), 100, money(30.0))),
+            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(at(3), 100, money(40.0))),
+        ];
+
+        let ticker = WalletTicker::new("BBAS3", &actions);
+        let gains = ticker.realized_gains(LotMatching::AverageCost).unwrap();
+
+        assert!((gains.realized.to_f64() - 1500.0).abs() < 0.01);
+        assert!((gains.cost_basis.to_f64() - 2500.0).abs() < 0.01);
+        assert_eq!(gains.sales.len(), 1);
+    }
+
+    #[test]
+    fn it_calculates_realized_gains_with_fifo_splitting_lots() {
+        let actions = vec![
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(at(1), 100, money(20.0))),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(at(2), 100, money(30.0))),
+            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(at(3), 150, money(40.0))),
+        ];
+
+        let ticker = WalletTicker::new("BBAS3", &actions);
+        let gains = ticker.realized_gains(LotMatching::Fifo).unwrap();
+
+        // 100 shares @ 20 + 50 shares @ 30 = 3500 cost basis consumed.
+        assert!((gains.cost_basis.to_f64() - 3500.0).abs() < 0.01);
+        assert!((gains.realized.to_f64() - 2500.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn it_sorts_events_by_date_before_matching_regardless_of_insertion_order() {
+        let actions = vec![
+            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(at(3), 100, money(40.0))),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(at(1), 100, money(20.0))),
+        ];
+
+        let ticker = WalletTicker::new("BBAS3", &actions);
+        let gains = ticker.realized_gains(LotMatching::Fifo).unwrap();
+
+        assert!((gains.realized.to_f64() - 2000.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn it_errors_when_selling_more_than_is_held() {
+        let actions = vec![
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(at(1), 100, money(20.0))),
+            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(at(2), 200, money(40.0))),
+        ];
+
+        let ticker = WalletTicker::new("BBAS3", &actions);
+
+        assert_eq!(
+            ticker.realized_gains(LotMatching::Fifo),
+            Err(RealizedGainsError::Oversold {
+                code: "BBAS3".to_owned(),
+                sold: 200,
+                held: 100,
+            })
+        );
+    }
+
+    #[test]
+    fn it_does_not_panic_on_a_zero_amount_buy_before_a_sell() {
+        let actions = vec![
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(at(1), 0, money(20.0))),
+            Event::Buy("BBAS3".to_owned(), TransactionInfo::new(at(2), 100, money(30.0))),
+            Event::Sell("BBAS3".to_owned(), TransactionInfo::new(at(3), 100, money(40.0))),
+        ];
+
+        let ticker = WalletTicker::new("BBAS3", &actions);
+        let gains = ticker.realized_gains(LotMatching::AverageCost).unwrap();
+
+        assert!((gains.realized.to_f64() - 1000.0).abs() < 0.01);
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("mock push failure for {0}")]
+    struct MockPushError(String);
+
+    struct MockOnline {
+        remote: Vec<Event>,
+        rejected_codes: Vec<&'static str>,
+        pushed: Mutex<Vec<Event>>,
+    }
+
+    impl OnlineWallet for MockOnline {
+        type Error = MockPushError;
+
+        fn add_asset(&self, event: Event) -> BoxFuture<'_, Result<(), Self::Error>> {
+            Box::pin(async move {
+                if self.rejected_codes.contains(&event.code()) {
+                    return Err(MockPushError(event.code().to_owned()));
+                }
+
+                self.pushed.lock().unwrap().push(event);
+                Ok(())
+            })
+        }
+
+        fn get_ticker_id(&self, _ticker: &str) -> BoxFuture<'_, Result<crate::online::Ticker, Self::Error>> {
+            unimplemented!("not exercised by sync")
+        }
+
+        fn fetch_trades(&self) -> BoxFuture<'_, Result<Vec<Event>, Self::Error>> {
+            Box::pin(async move { Ok(self.remote.clone()) })
+        }
+    }
+
+    fn at_time(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        at(day) + chrono::Duration::hours(hour as i64) + chrono::Duration::minutes(minute as i64)
+    }
+
+    #[tokio::test]
+    async fn it_does_not_repush_a_trade_whose_remote_echo_truncated_the_time_of_day() {
+        let local = Event::Buy("PETR4".to_owned(), TransactionInfo::new(at_time(1, 14, 30), 100, money(20.0)));
+        let wallet = Wallet::from_transactions(vec![local]);
+
+        let remote_echo = Event::Buy("PETR4".to_owned(), TransactionInfo::new(at(1), 100, money(20.0)));
+        let online = MockOnline {
+            remote: vec![remote_echo],
+            rejected_codes: Vec::new(),
+            pushed: Mutex::new(Vec::new()),
+        };
+
+        let report = wallet.sync(&online).await.unwrap();
+
+        assert_eq!(report.pushed, 0);
+        assert!(online.pushed.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_pushes_trades_that_are_missing_remotely() {
+        let local = Event::Buy("PETR4".to_owned(), TransactionInfo::new(at_time(1, 14, 30), 100, money(20.0)));
+        let wallet = Wallet::from_transactions(vec![local]);
+
+        let online = MockOnline {
+            remote: Vec::new(),
+            rejected_codes: Vec::new(),
+            pushed: Mutex::new(Vec::new()),
+        };
+
+        let report = wallet.sync(&online).await.unwrap();
+
+        assert_eq!(report.pushed, 1);
+        assert_eq!(online.pushed.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_keeps_pushing_other_tickers_after_one_push_fails() {
+        let local = vec![
+            Event::Buy("PETR4".to_owned(), TransactionInfo::new(at(1), 100, money(20.0))),
+            Event::Buy("VALE3".to_owned(), TransactionInfo::new(at(1), 50, money(70.0))),
+        ];
+        let wallet = Wallet::from_transactions(local);
+
+        let online = MockOnline {
+            remote: Vec::new(),
+            rejected_codes: vec!["PETR4"],
+            pushed: Mutex::new(Vec::new()),
+        };
+
+        let report = wallet.sync(&online).await.unwrap();
+
+        assert_eq!(report.pushed, 1);
+        assert_eq!(online.pushed.lock().unwrap().len(), 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0.code(), "PETR4");
+    }
 }